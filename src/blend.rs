@@ -0,0 +1,29 @@
+use image::{Rgba, RgbaImage};
+
+/// Straight alpha-over-alpha blend of `src` onto the pixel at `(x, y)`.
+pub fn blend_pixel(target: &mut RgbaImage, x: u32, y: u32, src: Rgba<u8>) {
+    let dst = *target.get_pixel(x, y);
+    let src_a = src[3] as f32 / 255.0;
+    if src_a <= 0.0 {
+        return;
+    }
+    let dst_a = dst[3] as f32 / 255.0;
+    let out_a = src_a + dst_a * (1.0 - src_a);
+
+    let blend_channel = |s: u8, d: u8| -> u8 {
+        if out_a <= 0.0 {
+            return 0;
+        }
+        let s = s as f32 / 255.0;
+        let d = d as f32 / 255.0;
+        (((s * src_a + d * dst_a * (1.0 - src_a)) / out_a) * 255.0).round() as u8
+    };
+
+    let blended = Rgba([
+        blend_channel(src[0], dst[0]),
+        blend_channel(src[1], dst[1]),
+        blend_channel(src[2], dst[2]),
+        (out_a * 255.0).round() as u8,
+    ]);
+    target.put_pixel(x, y, blended);
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+
+use crate::blend::blend_pixel;
+use crate::error::RustyMarkError;
+use crate::ColorConfig;
+
+/// A single glyph's location within its atlas page, as recorded by a
+/// `char id=... x=... y=...` line in a `.fnt` file.
+#[derive(Debug, Clone, Copy)]
+struct Glyph {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    xoffset: i32,
+    yoffset: i32,
+    xadvance: i32,
+    page: u32,
+}
+
+/// A parsed AngelCode BMFont (`.fnt` + one or more atlas images).
+#[derive(Debug)]
+pub struct BmFont {
+    line_height: u32,
+    pages: Vec<RgbaImage>,
+    glyphs: HashMap<u32, Glyph>,
+    kerning: HashMap<(u32, u32), i32>,
+    default_advance: i32,
+}
+
+// Split a BMFont attribute line into its tag and `key=value` pairs, honoring
+// double-quoted values (e.g. `file="atlas.png"`) that may contain spaces.
+fn tokenize(line: &str) -> (String, HashMap<String, String>) {
+    let mut chars = line.trim().chars().peekable();
+    let mut tag = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            break;
+        }
+        tag.push(c);
+        chars.next();
+    }
+
+    let rest: String = chars.collect();
+    let mut attrs = HashMap::new();
+    let mut iter = rest.trim().chars().peekable();
+    loop {
+        while matches!(iter.peek(), Some(c) if c.is_whitespace()) {
+            iter.next();
+        }
+        let mut key = String::new();
+        while matches!(iter.peek(), Some(&c) if c != '=' && !c.is_whitespace()) {
+            key.push(iter.next().unwrap());
+        }
+        if key.is_empty() {
+            break;
+        }
+        if iter.peek() != Some(&'=') {
+            break;
+        }
+        iter.next(); // consume '='
+
+        let mut value = String::new();
+        if iter.peek() == Some(&'"') {
+            iter.next();
+            while let Some(&c) = iter.peek() {
+                iter.next();
+                if c == '"' {
+                    break;
+                }
+                value.push(c);
+            }
+        } else {
+            while matches!(iter.peek(), Some(&c) if !c.is_whitespace()) {
+                value.push(iter.next().unwrap());
+            }
+        }
+
+        attrs.insert(key, value);
+    }
+
+    (tag, attrs)
+}
+
+fn attr<'a>(attrs: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    attrs.get(key).map(String::as_str)
+}
+
+fn attr_u32(attrs: &HashMap<String, String>, key: &str) -> Option<u32> {
+    attr(attrs, key).and_then(|v| v.parse().ok())
+}
+
+fn attr_i32(attrs: &HashMap<String, String>, key: &str) -> Option<i32> {
+    attr(attrs, key).and_then(|v| v.parse().ok())
+}
+
+impl BmFont {
+    /// Parse a `.fnt` descriptor and load its page atlases (resolved
+    /// relative to the descriptor's own directory).
+    pub fn load(fnt_path: &Path) -> Result<BmFont, RustyMarkError> {
+        let content = fs::read_to_string(fnt_path).map_err(|e| RustyMarkError::FontLoad {
+            path: fnt_path.to_path_buf(),
+            source: Box::new(e),
+        })?;
+
+        let base_dir = fnt_path.parent().unwrap_or_else(|| Path::new("."));
+
+        let mut line_height = 0u32;
+        let mut page_files: Vec<PathBuf> = Vec::new();
+        let mut glyphs = HashMap::new();
+        let mut kerning = HashMap::new();
+
+        for line in content.lines() {
+            let (tag, attrs) = tokenize(line);
+            match tag.as_str() {
+                "common" => {
+                    line_height = attr_u32(&attrs, "lineHeight").unwrap_or(0);
+                }
+                "page" => {
+                    let id = attr_u32(&attrs, "id").unwrap_or(0);
+                    let file = attr(&attrs, "file").unwrap_or_default().to_string();
+                    let index = id as usize;
+                    if page_files.len() <= index {
+                        page_files.resize(index + 1, PathBuf::new());
+                    }
+                    page_files[index] = base_dir.join(file);
+                }
+                "char" => {
+                    let id = attr_u32(&attrs, "id").unwrap_or(0);
+                    glyphs.insert(
+                        id,
+                        Glyph {
+                            x: attr_u32(&attrs, "x").unwrap_or(0),
+                            y: attr_u32(&attrs, "y").unwrap_or(0),
+                            width: attr_u32(&attrs, "width").unwrap_or(0),
+                            height: attr_u32(&attrs, "height").unwrap_or(0),
+                            xoffset: attr_i32(&attrs, "xoffset").unwrap_or(0),
+                            yoffset: attr_i32(&attrs, "yoffset").unwrap_or(0),
+                            xadvance: attr_i32(&attrs, "xadvance").unwrap_or(0),
+                            page: attr_u32(&attrs, "page").unwrap_or(0),
+                        },
+                    );
+                }
+                "kerning" => {
+                    let first = attr_u32(&attrs, "first").unwrap_or(0);
+                    let second = attr_u32(&attrs, "second").unwrap_or(0);
+                    let amount = attr_i32(&attrs, "amount").unwrap_or(0);
+                    kerning.insert((first, second), amount);
+                }
+                _ => {}
+            }
+        }
+
+        let mut pages = Vec::with_capacity(page_files.len());
+        for page_path in &page_files {
+            let page_image = image::open(page_path)
+                .map_err(|e| RustyMarkError::ImageOpen {
+                    path: page_path.clone(),
+                    source: e,
+                })?
+                .to_rgba8();
+            pages.push(page_image);
+        }
+
+        // A reasonable fallback advance for glyphs missing from the atlas
+        // (typically plain whitespace), roughly a quarter of the line height.
+        let default_advance = (line_height / 4).max(1) as i32;
+
+        Ok(BmFont {
+            line_height,
+            pages,
+            glyphs,
+            kerning,
+            default_advance,
+        })
+    }
+
+    /// Total rendered width/height of `text`, following the same cursor
+    /// advance logic as [`BmFont::draw`].
+    pub fn text_size(&self, text: &str) -> (u32, u32) {
+        let mut width = 0i32;
+        let mut prev: Option<u32> = None;
+
+        for c in text.chars() {
+            let id = c as u32;
+            let advance = match self.glyphs.get(&id) {
+                Some(glyph) => glyph.xadvance,
+                None => self.default_advance,
+            };
+            let kern = prev
+                .and_then(|p| self.kerning.get(&(p, id)))
+                .copied()
+                .unwrap_or(0);
+            width += advance + kern;
+            prev = Some(id);
+        }
+
+        (width.max(0) as u32, self.line_height)
+    }
+
+    /// Render `text` onto `target` with its top-left corner at `(x, y)`,
+    /// tinting each glyph with `color` and alpha-blending it onto the image.
+    pub fn draw(&self, target: &mut RgbaImage, x: i32, y: i32, text: &str, color: &ColorConfig) {
+        let mut cursor_x = x;
+        let mut prev: Option<u32> = None;
+
+        for c in text.chars() {
+            let id = c as u32;
+            let kern = prev
+                .and_then(|p| self.kerning.get(&(p, id)))
+                .copied()
+                .unwrap_or(0);
+            cursor_x += kern;
+
+            match self.glyphs.get(&id) {
+                Some(glyph) => {
+                    if let Some(page) = self.pages.get(glyph.page as usize) {
+                        self.blit_glyph(target, page, glyph, cursor_x, y, color);
+                    }
+                    cursor_x += glyph.xadvance;
+                }
+                None => cursor_x += self.default_advance,
+            }
+
+            prev = Some(id);
+        }
+    }
+
+    fn blit_glyph(
+        &self,
+        target: &mut RgbaImage,
+        page: &RgbaImage,
+        glyph: &Glyph,
+        cursor_x: i32,
+        cursor_y: i32,
+        color: &ColorConfig,
+    ) {
+        let (target_width, target_height) = target.dimensions();
+        let dest_x0 = cursor_x + glyph.xoffset;
+        let dest_y0 = cursor_y + glyph.yoffset;
+
+        for row in 0..glyph.height {
+            for col in 0..glyph.width {
+                let src_x = glyph.x + col;
+                let src_y = glyph.y + row;
+                if src_x >= page.dimensions().0 || src_y >= page.dimensions().1 {
+                    continue;
+                }
+
+                let dest_x = dest_x0 + col as i32;
+                let dest_y = dest_y0 + row as i32;
+                if dest_x < 0 || dest_y < 0 || dest_x as u32 >= target_width || dest_y as u32 >= target_height {
+                    continue;
+                }
+
+                let src_pixel = page.get_pixel(src_x, src_y);
+                let alpha = (src_pixel[3] as u16 * color.a as u16 / 255) as u8;
+                if alpha == 0 {
+                    continue;
+                }
+
+                let tinted = Rgba([color.r, color.g, color.b, alpha]);
+                blend_pixel(target, dest_x as u32, dest_y as u32, tinted);
+            }
+        }
+    }
+}
+
+/// Whether `font_path` points at an AngelCode BMFont descriptor rather than
+/// a scalable TTF/OTF font.
+pub fn is_bmfont(font_path: &Path) -> bool {
+    font_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("fnt"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_reads_tag_and_plain_attrs() {
+        let (tag, attrs) = tokenize("char id=65 x=1 y=2 width=10");
+        assert_eq!(tag, "char");
+        assert_eq!(attr(&attrs, "id"), Some("65"));
+        assert_eq!(attr(&attrs, "x"), Some("1"));
+        assert_eq!(attr(&attrs, "width"), Some("10"));
+    }
+
+    #[test]
+    fn tokenize_honors_quoted_values_with_spaces() {
+        let (tag, attrs) = tokenize(r#"page id=0 file="atlas 01.png""#);
+        assert_eq!(tag, "page");
+        assert_eq!(attr(&attrs, "file"), Some("atlas 01.png"));
+    }
+
+    #[test]
+    fn tokenize_ignores_malformed_trailing_garbage() {
+        let (tag, attrs) = tokenize("common lineHeight=32 junk");
+        assert_eq!(tag, "common");
+        assert_eq!(attr_u32(&attrs, "lineHeight"), Some(32));
+        assert_eq!(attrs.get("junk"), None);
+    }
+
+    #[test]
+    fn tokenize_empty_line_has_no_tag_or_attrs() {
+        let (tag, attrs) = tokenize("   ");
+        assert!(tag.is_empty());
+        assert!(attrs.is_empty());
+    }
+
+    fn glyph(xadvance: i32) -> Glyph {
+        Glyph {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            xoffset: 0,
+            yoffset: 0,
+            xadvance,
+            page: 0,
+        }
+    }
+
+    #[test]
+    fn text_size_sums_advances_and_applies_kerning() {
+        let mut glyphs = HashMap::new();
+        glyphs.insert('A' as u32, glyph(10));
+        glyphs.insert('V' as u32, glyph(8));
+
+        let mut kerning = HashMap::new();
+        kerning.insert(('A' as u32, 'V' as u32), -2);
+
+        let font = BmFont {
+            line_height: 16,
+            pages: Vec::new(),
+            glyphs,
+            kerning,
+            default_advance: 4,
+        };
+
+        let (width, height) = font.text_size("AV");
+        assert_eq!(width, 10 + 8 - 2);
+        assert_eq!(height, 16);
+    }
+
+    #[test]
+    fn text_size_falls_back_to_default_advance_for_missing_glyphs() {
+        let font = BmFont {
+            line_height: 16,
+            pages: Vec::new(),
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            default_advance: 5,
+        };
+
+        let (width, _) = font.text_size("  ");
+        assert_eq!(width, 10);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::error::RustyMarkError;
+use crate::format::OutputFormat;
+
+// Configuration structure for copyright settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct CopyrightConfig {
+    #[serde(default = "default_text")]
+    pub text: String,
+
+    #[serde(default = "default_font_path")]
+    pub font_path: PathBuf,
+
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+
+    #[serde(default = "default_mode")]
+    pub mode: WatermarkMode,
+
+    #[serde(default = "default_color")]
+    pub color: ColorConfig,
+
+    /// Output image format; defaults to `None`, meaning "keep the input's
+    /// own format".
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+
+    #[serde(default = "default_background")]
+    pub background: ColorConfig,
+
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+}
+
+// Default value functions
+fn default_text() -> String {
+    "Â© Copyright".to_string()
+}
+
+fn default_font_path() -> PathBuf {
+    PathBuf::from("/path/to/default/font.ttf")
+}
+
+fn default_font_size() -> f32 {
+    20.0
+}
+
+fn default_position() -> Position {
+    Position::BottomRight
+}
+
+fn default_mode() -> WatermarkMode {
+    WatermarkMode::Single(default_position())
+}
+
+fn default_color() -> ColorConfig {
+    ColorConfig {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 128,
+    }
+}
+
+// Opaque white, used to flatten transparency when converting to a format
+// without an alpha channel (e.g. JPEG).
+fn default_background() -> ColorConfig {
+    ColorConfig {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    }
+}
+
+fn default_quality() -> u8 {
+    90
+}
+
+pub fn default_config() -> CopyrightConfig {
+    CopyrightConfig {
+        text: default_text(),
+        font_path: default_font_path(),
+        font_size: default_font_size(),
+        mode: default_mode(),
+        color: default_color(),
+        format: None,
+        background: default_background(),
+        quality: default_quality(),
+    }
+}
+
+// Separate struct for color configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColorConfig {
+    #[serde(default = "default_color_component")]
+    pub r: u8,
+    #[serde(default = "default_color_component")]
+    pub g: u8,
+    #[serde(default = "default_color_component")]
+    pub b: u8,
+    #[serde(default = "default_alpha")]
+    pub a: u8,
+}
+
+fn default_color_component() -> u8 {
+    255
+}
+
+fn default_alpha() -> u8 {
+    128
+}
+
+impl FromStr for ColorConfig {
+    type Err = String;
+
+    // Accepts "r,g,b" or "r,g,b,a"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(format!("expected \"r,g,b\" or \"r,g,b,a\", got \"{}\"", s));
+        }
+
+        let parse_component = |value: &str| {
+            value
+                .parse::<u8>()
+                .map_err(|e| format!("invalid color component \"{}\": {}", value, e))
+        };
+
+        Ok(ColorConfig {
+            r: parse_component(parts[0])?,
+            g: parse_component(parts[1])?,
+            b: parse_component(parts[2])?,
+            a: if parts.len() == 4 {
+                parse_component(parts[3])?
+            } else {
+                default_alpha()
+            },
+        })
+    }
+}
+
+// Enum for positioning the copyright text
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Position {
+    #[serde(rename = "top_left")]
+    TopLeft,
+    #[serde(rename = "top_center")]
+    TopCenter,
+    #[serde(rename = "top_right")]
+    TopRight,
+    #[serde(rename = "middle_left")]
+    MiddleLeft,
+    #[serde(rename = "middle_center")]
+    MiddleCenter,
+    #[serde(rename = "middle_right")]
+    MiddleRight,
+    #[serde(rename = "bottom_left")]
+    BottomLeft,
+    #[serde(rename = "bottom_center")]
+    BottomCenter,
+    #[serde(rename = "bottom_right")]
+    BottomRight,
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top_left" => Ok(Position::TopLeft),
+            "top_center" => Ok(Position::TopCenter),
+            "top_right" => Ok(Position::TopRight),
+            "middle_left" => Ok(Position::MiddleLeft),
+            "middle_center" => Ok(Position::MiddleCenter),
+            "middle_right" => Ok(Position::MiddleRight),
+            "bottom_left" => Ok(Position::BottomLeft),
+            "bottom_center" => Ok(Position::BottomCenter),
+            "bottom_right" => Ok(Position::BottomRight),
+            other => Err(format!("unknown position \"{}\"", other)),
+        }
+    }
+}
+
+/// How the watermark is placed: once at a fixed anchor, or repeated across
+/// the whole image on a grid.
+///
+/// Deserializes from either a plain position string (`mode = "bottom_right"`)
+/// or a table giving the tile grid (`mode = { angle = 15.0, spacing_x = 150,
+/// spacing_y = 150 }`) — whichever shape matches what's in the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum WatermarkMode {
+    Tiled {
+        #[serde(default)]
+        angle: f32,
+        spacing_x: u32,
+        spacing_y: u32,
+    },
+    Single(Position),
+}
+
+// Every field optional, with no defaults applied: distinguishes "not set by
+// any file in the import chain" from "explicitly set", which plain
+// `CopyrightConfig` (defaults filled in at deserialize time) cannot.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RawCopyrightConfig {
+    #[serde(default)]
+    import: Vec<PathBuf>,
+    text: Option<String>,
+    font_path: Option<PathBuf>,
+    font_size: Option<f32>,
+    mode: Option<WatermarkMode>,
+    color: Option<ColorConfig>,
+    format: Option<OutputFormat>,
+    background: Option<ColorConfig>,
+    quality: Option<u8>,
+}
+
+impl RawCopyrightConfig {
+    // `overlay`'s set fields win; anything it leaves unset falls back to `self`.
+    fn merged_with(self, overlay: RawCopyrightConfig) -> RawCopyrightConfig {
+        RawCopyrightConfig {
+            import: overlay.import,
+            text: overlay.text.or(self.text),
+            font_path: overlay.font_path.or(self.font_path),
+            font_size: overlay.font_size.or(self.font_size),
+            mode: overlay.mode.or(self.mode),
+            color: overlay.color.or(self.color),
+            format: overlay.format.or(self.format),
+            background: overlay.background.or(self.background),
+            quality: overlay.quality.or(self.quality),
+        }
+    }
+
+    fn into_config(self) -> CopyrightConfig {
+        CopyrightConfig {
+            text: self.text.unwrap_or_else(default_text),
+            font_path: self.font_path.unwrap_or_else(default_font_path),
+            font_size: self.font_size.unwrap_or_else(default_font_size),
+            mode: self.mode.unwrap_or_else(default_mode),
+            color: self.color.unwrap_or_else(default_color),
+            format: self.format,
+            background: self.background.unwrap_or_else(default_background),
+            quality: self.quality.unwrap_or_else(default_quality),
+        }
+    }
+}
+
+// Read and deserialize one config file, dispatching on its extension.
+fn load_raw(config_path: &Path) -> Result<RawCopyrightConfig, RustyMarkError> {
+    let content = fs::read_to_string(config_path).map_err(|e| RustyMarkError::ConfigParse {
+        path: config_path.to_path_buf(),
+        source: Box::new(e),
+    })?;
+
+    let to_config_error = |source: Box<dyn std::error::Error + Send + Sync>| RustyMarkError::ConfigParse {
+        path: config_path.to_path_buf(),
+        source,
+    };
+
+    match config_path.extension().and_then(|ext| ext.to_str()).unwrap_or("") {
+        "json" => serde_json::from_str(&content).map_err(|e| to_config_error(Box::new(e))),
+        "yaml" | "yml" => serde_yaml::from_str(&content).map_err(|e| to_config_error(Box::new(e))),
+        _ => toml::from_str(&content).map_err(|e| to_config_error(Box::new(e))),
+    }
+}
+
+// Load `config_path`, recursively loading and merging every config named in
+// its `import` list (resolved relative to `config_path`'s own directory)
+// before applying this file's own fields on top. The merge works one
+// top-level key at a time: a table such as `color` or `mode` is taken whole
+// from the last file that sets it, and its inner keys are not merged across
+// files. `visiting`
+// tracks the canonicalized paths currently on the stack so a config that
+// (directly or transitively) imports itself is rejected instead of
+// recursing forever.
+fn load_chain(config_path: &Path, visiting: &mut HashSet<PathBuf>) -> Result<RawCopyrightConfig, RustyMarkError> {
+    let canonical = fs::canonicalize(config_path).map_err(|e| RustyMarkError::ConfigParse {
+        path: config_path.to_path_buf(),
+        source: Box::new(e),
+    })?;
+    if !visiting.insert(canonical.clone()) {
+        return Err(RustyMarkError::ImportCycle {
+            path: config_path.to_path_buf(),
+        });
+    }
+
+    let raw = load_raw(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut merged = RawCopyrightConfig::default();
+    for import_path in &raw.import {
+        let resolved = base_dir.join(import_path);
+        let imported = load_chain(&resolved, visiting)?;
+        merged = merged.merged_with(imported);
+    }
+    merged = merged.merged_with(raw);
+
+    visiting.remove(&canonical);
+
+    Ok(merged)
+}
+
+/// Parse configuration from a TOML, JSON or YAML file, resolving any
+/// `import` chain and filling whatever no file in the chain set with the
+/// built-in defaults.
+pub fn parse_config(config_path: &Path) -> Result<CopyrightConfig, RustyMarkError> {
+    let mut visiting = HashSet::new();
+    Ok(load_chain(config_path, &mut visiting)?.into_config())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_with_prefers_overlay_fields() {
+        let base = RawCopyrightConfig {
+            text: Some("base".to_string()),
+            font_size: Some(10.0),
+            ..Default::default()
+        };
+        let overlay = RawCopyrightConfig {
+            text: Some("overlay".to_string()),
+            ..Default::default()
+        };
+
+        let merged = base.merged_with(overlay);
+        assert_eq!(merged.text, Some("overlay".to_string()));
+        assert_eq!(merged.font_size, Some(10.0));
+    }
+
+    #[test]
+    fn merged_with_keeps_base_field_when_overlay_leaves_it_unset() {
+        let base = RawCopyrightConfig {
+            quality: Some(42),
+            ..Default::default()
+        };
+        let overlay = RawCopyrightConfig::default();
+
+        let merged = base.merged_with(overlay);
+        assert_eq!(merged.quality, Some(42));
+    }
+
+    // A scratch directory under the OS temp dir, torn down on drop, so
+    // concurrently-run tests don't trip over each other's fixture files.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir = std::env::temp_dir().join(format!("rustymark-config-test-{}", name));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, file_name: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(file_name);
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn load_chain_detects_self_import_cycle() {
+        let dir = TempDir::new("self-cycle");
+        let path = dir.write("a.toml", "import = [\"a.toml\"]\n");
+
+        let result = parse_config(&path);
+        assert!(matches!(result, Err(RustyMarkError::ImportCycle { .. })));
+    }
+
+    #[test]
+    fn load_chain_detects_mutual_import_cycle() {
+        let dir = TempDir::new("mutual-cycle");
+        dir.write("a.toml", "import = [\"b.toml\"]\n");
+        let b_path = dir.write("b.toml", "import = [\"a.toml\"]\n");
+
+        let result = parse_config(&b_path);
+        assert!(matches!(result, Err(RustyMarkError::ImportCycle { .. })));
+    }
+
+    #[test]
+    fn load_chain_merges_diamond_import_without_false_cycle() {
+        let dir = TempDir::new("diamond");
+        dir.write("base.toml", "text = \"from base\"\n");
+        dir.write("left.toml", "import = [\"base.toml\"]\n");
+        dir.write("right.toml", "import = [\"base.toml\"]\nfont_size = 30.0\n");
+        let top = dir.write(
+            "top.toml",
+            "import = [\"left.toml\", \"right.toml\"]\ntext = \"from top\"\n",
+        );
+
+        let config = parse_config(&top).expect("diamond import should not be a cycle");
+        assert_eq!(config.text, "from top");
+        assert_eq!(config.font_size, 30.0);
+    }
+}
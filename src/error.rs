@@ -0,0 +1,77 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// An error produced while loading a font, image or config file, with enough
+/// context (the offending path) to build a useful summary line.
+#[derive(Debug)]
+pub enum RustyMarkError {
+    FontLoad {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    ImageOpen {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    ConfigParse {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    Encode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    ImportCycle {
+        path: PathBuf,
+    },
+    Other(String),
+}
+
+/// The font file loaded successfully but its contents could not be parsed
+/// as a font (`rusttype::Font::try_from_vec` only reports failure, no cause).
+#[derive(Debug)]
+pub struct InvalidFontData;
+
+impl fmt::Display for InvalidFontData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid or unsupported font data")
+    }
+}
+
+impl std::error::Error for InvalidFontData {}
+
+impl fmt::Display for RustyMarkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RustyMarkError::FontLoad { path, source } => {
+                write!(f, "failed to load font {}: {}", path.display(), source)
+            }
+            RustyMarkError::ImageOpen { path, source } => {
+                write!(f, "failed to open image {}: {}", path.display(), source)
+            }
+            RustyMarkError::ConfigParse { path, source } => {
+                write!(f, "failed to parse config {}: {}", path.display(), source)
+            }
+            RustyMarkError::Encode { path, source } => {
+                write!(f, "failed to encode output {}: {}", path.display(), source)
+            }
+            RustyMarkError::ImportCycle { path } => {
+                write!(f, "config import cycle detected at {}", path.display())
+            }
+            RustyMarkError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for RustyMarkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RustyMarkError::FontLoad { source, .. } => Some(source.as_ref()),
+            RustyMarkError::ImageOpen { source, .. } => Some(source),
+            RustyMarkError::ConfigParse { source, .. } => Some(source.as_ref()),
+            RustyMarkError::Encode { source, .. } => Some(source),
+            RustyMarkError::ImportCycle { .. } => None,
+            RustyMarkError::Other(_) => None,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ImageEncoder, RgbImage, RgbaImage};
+use serde::Deserialize;
+
+use crate::error::RustyMarkError;
+use crate::ColorConfig;
+
+/// Whether `ext` names a file extension RustyMark treats as an image, i.e.
+/// one [`OutputFormat::from_extension`] recognizes. Shared by `is_image_file`
+/// (reading) and [`OutputFormat`] (writing) so there is exactly one list of
+/// supported extensions rather than two that can drift apart.
+pub fn is_supported_extension(ext: &str) -> bool {
+    OutputFormat::from_extension(ext).is_some()
+}
+
+/// Output image format a watermarked file can be converted to, independent
+/// of whatever format the input happened to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OutputFormat {
+    #[serde(rename = "png")]
+    Png,
+    #[serde(rename = "jpeg")]
+    Jpeg,
+    #[serde(rename = "webp")]
+    WebP,
+    #[serde(rename = "bmp")]
+    Bmp,
+    #[serde(rename = "gif")]
+    Gif,
+    #[serde(rename = "tiff")]
+    Tiff,
+}
+
+impl OutputFormat {
+    pub fn from_extension(ext: &str) -> Option<OutputFormat> {
+        match ext.to_lowercase().as_str() {
+            "png" => Some(OutputFormat::Png),
+            "jpg" | "jpeg" => Some(OutputFormat::Jpeg),
+            "webp" => Some(OutputFormat::WebP),
+            "bmp" => Some(OutputFormat::Bmp),
+            "gif" => Some(OutputFormat::Gif),
+            "tiff" | "tif" => Some(OutputFormat::Tiff),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Bmp => "bmp",
+            OutputFormat::Gif => "gif",
+            OutputFormat::Tiff => "tiff",
+        }
+    }
+
+    fn image_format(&self) -> image::ImageFormat {
+        match self {
+            OutputFormat::Png => image::ImageFormat::Png,
+            OutputFormat::Jpeg => image::ImageFormat::Jpeg,
+            OutputFormat::WebP => image::ImageFormat::WebP,
+            OutputFormat::Bmp => image::ImageFormat::Bmp,
+            OutputFormat::Gif => image::ImageFormat::Gif,
+            OutputFormat::Tiff => image::ImageFormat::Tiff,
+        }
+    }
+
+    /// Whether this format can keep the watermark's alpha channel, or needs
+    /// the image flattened onto an opaque background first.
+    pub fn supports_alpha(&self) -> bool {
+        matches!(self, OutputFormat::Png | OutputFormat::WebP | OutputFormat::Gif | OutputFormat::Tiff)
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputFormat::from_extension(s).ok_or_else(|| format!("unsupported output format \"{}\"", s))
+    }
+}
+
+// Alpha-composite `image` over a solid `background`, discarding the alpha
+// channel, for formats (like JPEG) that can't store transparency.
+fn flatten_on_background(image: &RgbaImage, background: &ColorConfig) -> RgbImage {
+    let (width, height) = image.dimensions();
+    let mut flattened = RgbImage::new(width, height);
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let src_a = pixel[3] as f32 / 255.0;
+        let blend = |src: u8, bg: u8| -> u8 {
+            ((src as f32 * src_a) + (bg as f32 * (1.0 - src_a))).round() as u8
+        };
+        flattened.put_pixel(
+            x,
+            y,
+            image::Rgb([
+                blend(pixel[0], background.r),
+                blend(pixel[1], background.g),
+                blend(pixel[2], background.b),
+            ]),
+        );
+    }
+
+    flattened
+}
+
+/// Encode `image` to `output_path` in `format`, flattening onto
+/// `background` first if the format can't hold an alpha channel, and
+/// honoring `quality` for encoders that support a quality/compression knob.
+pub fn encode(
+    image: &RgbaImage,
+    format: OutputFormat,
+    background: &ColorConfig,
+    quality: u8,
+    output_path: &Path,
+) -> Result<(), RustyMarkError> {
+    let open_writer = || {
+        std::fs::File::create(output_path)
+            .map(std::io::BufWriter::new)
+            .map_err(|e| RustyMarkError::Encode {
+                path: output_path.to_path_buf(),
+                source: image::ImageError::IoError(e),
+            })
+    };
+
+    let result = match format {
+        OutputFormat::Jpeg => {
+            let flattened = flatten_on_background(image, background);
+            JpegEncoder::new_with_quality(open_writer()?, quality).write_image(
+                &flattened,
+                flattened.width(),
+                flattened.height(),
+                image::ColorType::Rgb8,
+            )
+        }
+        OutputFormat::Png => {
+            // Map the 0-100 `quality` knob onto png's compression levels:
+            // higher "quality" means spend more effort for a smaller file.
+            let compression = if quality >= 75 {
+                CompressionType::Best
+            } else if quality >= 25 {
+                CompressionType::Default
+            } else {
+                CompressionType::Fast
+            };
+            PngEncoder::new_with_quality(open_writer()?, compression, FilterType::Adaptive)
+                .write_image(image, image.width(), image.height(), image::ColorType::Rgba8)
+        }
+        _ if format.supports_alpha() => {
+            image.write_to(&mut open_writer()?, format.image_format())
+        }
+        _ => {
+            let flattened = flatten_on_background(image, background);
+            flattened.write_to(&mut open_writer()?, format.image_format())
+        }
+    };
+
+    result.map_err(|e| RustyMarkError::Encode {
+        path: output_path.to_path_buf(),
+        source: e,
+    })
+}
@@ -2,209 +2,402 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use std::error::Error;
 
+use clap::Parser;
 use image::{DynamicImage, GenericImageView, Rgba};
 use imageproc::drawing::{draw_text_mut, text_size};
 use rusttype::{Font, Scale};
-use serde::Deserialize;
-
-// Configuration structure for copyright settings
-#[derive(Debug, Clone, Deserialize)]
-struct CopyrightConfig {
-    #[serde(default = "default_text")]
-    text: String,
-    
-    #[serde(default = "default_font_path")]
-    font_path: PathBuf,
-    
-    #[serde(default = "default_font_size")]
-    font_size: f32,
-    
-    #[serde(default = "default_position")]
-    position: Position,
-    
-    #[serde(default = "default_color")]
-    color: ColorConfig,
-}
 
-// Default value functions
-fn default_text() -> String {
-    "Â© Copyright".to_string()
-}
+mod blend;
+mod bmfont;
+mod config;
+mod error;
+mod format;
+mod tile;
+use bmfont::BmFont;
+use config::{default_config, parse_config, ColorConfig, CopyrightConfig, Position, WatermarkMode};
+use error::{InvalidFontData, RustyMarkError};
+use format::OutputFormat;
 
-fn default_font_path() -> PathBuf {
-    PathBuf::from("/path/to/default/font.ttf")
-}
+/// Add a copyright watermark to a single image or every image in a directory.
+///
+/// Any flag given on the command line overrides the matching field in the
+/// config file; the config file itself is optional and falls back to the
+/// built-in defaults.
+#[derive(Parser, Debug)]
+#[command(name = "rustymark", version, about, long_about = None)]
+struct Cli {
+    /// Image file or directory to watermark
+    input: PathBuf,
 
-fn default_font_size() -> f32 {
-    20.0
-}
+    /// TOML, JSON or YAML config file (may `import` parent configs); when
+    /// omitted, built-in defaults (plus any overriding flags) are used
+    config: Option<PathBuf>,
 
-fn default_position() -> Position {
-    Position::BottomRight
-}
+    /// Override the watermark text
+    #[arg(long)]
+    text: Option<String>,
 
-fn default_color() -> ColorConfig {
-    ColorConfig {
-        r: 255,
-        g: 255,
-        b: 255,
-        a: 128,
-    }
-}
+    /// Override the font file used to render the watermark
+    #[arg(long)]
+    font: Option<PathBuf>,
 
-// Separate struct for color configuration
-#[derive(Debug, Clone, Deserialize)]
-struct ColorConfig {
-    #[serde(default = "default_color_component")]
-    r: u8,
-    #[serde(default = "default_color_component")]
-    g: u8,
-    #[serde(default = "default_color_component")]
-    b: u8,
-    #[serde(default = "default_alpha")]
-    a: u8,
-}
+    /// Override the font size
+    #[arg(long = "font-size")]
+    font_size: Option<f32>,
+
+    /// Override the watermark position (e.g. bottom_right, top_left); ignored if --tile is given
+    #[arg(long)]
+    position: Option<Position>,
+
+    /// Repeat the watermark across the whole image instead of placing it once
+    #[arg(long)]
+    tile: bool,
+
+    /// Rotation angle in degrees for tiled watermarks
+    #[arg(long = "tile-angle")]
+    tile_angle: Option<f32>,
+
+    /// Horizontal spacing in pixels between tiled watermark copies
+    #[arg(long = "tile-spacing-x")]
+    tile_spacing_x: Option<u32>,
+
+    /// Vertical spacing in pixels between tiled watermark copies
+    #[arg(long = "tile-spacing-y")]
+    tile_spacing_y: Option<u32>,
+
+    /// Override the watermark color as "r,g,b" or "r,g,b,a"
+    #[arg(long)]
+    color: Option<ColorConfig>,
+
+    /// Convert output to this format regardless of the input's own (png, jpeg, webp, bmp, gif, tiff)
+    #[arg(long)]
+    format: Option<OutputFormat>,
+
+    /// Background color used to flatten transparency for formats without an alpha channel, as "r,g,b"
+    #[arg(long)]
+    background: Option<ColorConfig>,
+
+    /// Quality/compression level (0-100) passed to encoders that support one
+    #[arg(long)]
+    quality: Option<u8>,
+
+    /// Directory to write watermarked images into (defaults to alongside the input)
+    #[arg(long = "output-dir")]
+    output_dir: Option<PathBuf>,
+
+    /// Prefix added to the output file name
+    #[arg(long, default_value = "watermarked_")]
+    prefix: String,
 
-fn default_color_component() -> u8 {
-    255
+    /// Report what would be processed without writing any files
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
+    /// Abort on the first failed file instead of continuing the batch
+    #[arg(long = "fail-fast")]
+    fail_fast: bool,
 }
 
-fn default_alpha() -> u8 {
-    128
+// Options controlling where/how output is written, independent of the
+// watermark styling itself.
+#[derive(Debug, Clone)]
+struct RunOptions {
+    output_dir: Option<PathBuf>,
+    prefix: String,
+    dry_run: bool,
+    fail_fast: bool,
 }
 
-// Enum for positioning the copyright text
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-enum Position {
-    #[serde(rename = "top_left")]
-    TopLeft,
-    #[serde(rename = "top_center")]
-    TopCenter,
-    #[serde(rename = "top_right")]
-    TopRight,
-    #[serde(rename = "middle_left")]
-    MiddleLeft,
-    #[serde(rename = "middle_center")]
-    MiddleCenter,
-    #[serde(rename = "middle_right")]
-    MiddleRight,
-    #[serde(rename = "bottom_left")]
-    BottomLeft,
-    #[serde(rename = "bottom_center")]
-    BottomCenter,
-    #[serde(rename = "bottom_right")]
-    BottomRight,
+// Tally of a directory batch run, printed once processing finishes.
+#[derive(Debug, Default)]
+struct BatchSummary {
+    succeeded: usize,
+    failed: Vec<(PathBuf, RustyMarkError)>,
 }
 
-// Parse configuration from a TOML file
-fn parse_config(config_path: &Path) -> Result<CopyrightConfig, Box<dyn Error>> {
-    let config_content = fs::read_to_string(config_path)?;
-    let config: CopyrightConfig = toml::from_str(&config_content)?;
+impl BatchSummary {
+    fn record_success(&mut self) {
+        self.succeeded += 1;
+    }
 
-    Ok(config)
+    fn record_failure(&mut self, path: PathBuf, error: RustyMarkError) {
+        self.failed.push((path, error));
+    }
+
+    fn print(&self) {
+        println!(
+            "Processed {} image(s): {} succeeded, {} failed",
+            self.succeeded + self.failed.len(),
+            self.succeeded,
+            self.failed.len()
+        );
+        for (path, error) in &self.failed {
+            println!("  {}: {}", path.display(), error);
+        }
+    }
+}
+
+// Apply any CLI-provided overrides on top of a parsed (or default) config.
+fn apply_cli_overrides(config: &mut CopyrightConfig, cli: &Cli) {
+    if let Some(text) = &cli.text {
+        config.text = text.clone();
+    }
+    if let Some(font) = &cli.font {
+        config.font_path = font.clone();
+    }
+    if let Some(font_size) = cli.font_size {
+        config.font_size = font_size;
+    }
+    let config_tile = match &config.mode {
+        WatermarkMode::Tiled { angle, spacing_x, spacing_y } => Some((*angle, *spacing_x, *spacing_y)),
+        WatermarkMode::Single(_) => None,
+    };
+    if cli.tile || (config_tile.is_some() && cli.position.is_none()) {
+        let (angle, spacing_x, spacing_y) = config_tile.unwrap_or((0.0, 200, 200));
+        config.mode = WatermarkMode::Tiled {
+            angle: cli.tile_angle.unwrap_or(angle),
+            spacing_x: cli.tile_spacing_x.unwrap_or(spacing_x),
+            spacing_y: cli.tile_spacing_y.unwrap_or(spacing_y),
+        };
+    } else if let Some(position) = cli.position {
+        config.mode = WatermarkMode::Single(position);
+    }
+    if let Some(color) = &cli.color {
+        config.color = color.clone();
+    }
+    if let Some(format) = cli.format {
+        config.format = Some(format);
+    }
+    if let Some(background) = &cli.background {
+        config.background = background.clone();
+    }
+    if let Some(quality) = cli.quality {
+        config.quality = quality;
+    }
 }
 
 // Calculate text position based on selected position
 fn calculate_text_position(
-    image: &DynamicImage, 
-    text_width: u32, 
-    text_height: u32, 
+    image: &DynamicImage,
+    text_width: u32,
+    text_height: u32,
     position: &Position
 ) -> (i32, i32) {
     let (img_width, img_height) = image.dimensions();
-    
+
     match position {
         Position::TopLeft => (10, 10),
         Position::TopCenter => ((img_width - text_width) as i32 / 2, 10),
         Position::TopRight => ((img_width - text_width) as i32 - 10, 10),
         Position::MiddleLeft => (10, (img_height - text_height) as i32 / 2),
         Position::MiddleCenter => (
-            (img_width - text_width) as i32 / 2, 
+            (img_width - text_width) as i32 / 2,
             (img_height - text_height) as i32 / 2
         ),
         Position::MiddleRight => (
-            (img_width - text_width) as i32 - 10, 
+            (img_width - text_width) as i32 - 10,
             (img_height - text_height) as i32 / 2
         ),
         Position::BottomLeft => (10, (img_height - text_height) as i32 - 10),
         Position::BottomCenter => (
-            (img_width - text_width) as i32 / 2, 
+            (img_width - text_width) as i32 / 2,
             (img_height - text_height) as i32 - 10
         ),
         Position::BottomRight => (
-            (img_width - text_width) as i32 - 10, 
+            (img_width - text_width) as i32 - 10,
             (img_height - text_height) as i32 - 10
         ),
     }
 }
 
+// The two supported ways of drawing the watermark text: a scalable TTF/OTF
+// font via `rusttype`, or a pre-rendered AngelCode BMFont atlas.
+enum TextRenderer {
+    Scalable(Font<'static>, Scale),
+    Bitmap(BmFont),
+}
+
+// Draw `config.text` at `(x, y)` onto `target` using whichever renderer was
+// selected for `config.font_path`.
+fn draw_text(target: &mut image::RgbaImage, renderer: &TextRenderer, x: i32, y: i32, config: &CopyrightConfig) {
+    match renderer {
+        TextRenderer::Scalable(font, scale) => {
+            draw_text_mut(
+                target,
+                Rgba([config.color.r, config.color.g, config.color.b, config.color.a]),
+                x,
+                y,
+                *scale,
+                font,
+                &config.text
+            );
+        }
+        TextRenderer::Bitmap(bmfont) => {
+            bmfont.draw(target, x, y, &config.text, &config.color);
+        }
+    }
+}
+
+// Work out where a watermarked copy of `image_path` should be written,
+// honoring an overridden output directory, file-name prefix, and output
+// format (which may change the file extension).
+fn compute_output_path(image_path: &Path, options: &RunOptions, output_format: OutputFormat) -> PathBuf {
+    let stem = image_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let file_name = format!("{}{}.{}", options.prefix, stem, output_format.extension());
+
+    match &options.output_dir {
+        Some(dir) => dir.join(file_name),
+        None => image_path.with_file_name(file_name),
+    }
+}
+
 // Add copyright text to an image
 fn add_copyright_text_image(
-    image_path: &Path, 
-    config: &CopyrightConfig
-) -> Result<(), Box<dyn Error>> {
-    // Load the font
-    let font_data = fs::read(&config.font_path)?;
-    let font = Font::try_from_vec(font_data)
-        .ok_or("Error loading font")?;
-
+    image_path: &Path,
+    config: &CopyrightConfig,
+    options: &RunOptions,
+) -> Result<(), RustyMarkError> {
     // Load the image
-    let image = image::open(image_path)?;
+    let image = image::open(image_path).map_err(|e| RustyMarkError::ImageOpen {
+        path: image_path.to_path_buf(),
+        source: e,
+    })?;
 
-    // Create scale for the font
-    let scale = Scale::uniform(config.font_size);
+    // Bitmap fonts (`.fnt` + atlas) and scalable TTF/OTF fonts are rendered
+    // through entirely different pipelines, so branch once up front.
+    let renderer = if bmfont::is_bmfont(&config.font_path) {
+        TextRenderer::Bitmap(BmFont::load(&config.font_path)?)
+    } else {
+        let font_data = fs::read(&config.font_path).map_err(|e| RustyMarkError::FontLoad {
+            path: config.font_path.clone(),
+            source: Box::new(e),
+        })?;
+        let font = Font::try_from_vec(font_data).ok_or_else(|| RustyMarkError::FontLoad {
+            path: config.font_path.clone(),
+            source: Box::new(InvalidFontData),
+        })?;
+        TextRenderer::Scalable(font, Scale::uniform(config.font_size))
+    };
 
     // Calculate text size
-    let (text_width, text_height) = text_size(scale, &font, &config.text);
+    let (text_width, text_height) = match &renderer {
+        TextRenderer::Scalable(font, scale) => text_size(*scale, font, &config.text),
+        TextRenderer::Bitmap(bmfont) => {
+            let (w, h) = bmfont.text_size(&config.text);
+            (w as i32, h as i32)
+        }
+    };
 
-    // Calculate text position
-    let (x, y) = calculate_text_position(&image, text_width as u32, text_height as u32, &config.position);
+    // For a single placement this is the final draw position; a tiled
+    // watermark doesn't use it (its stamp is sized from `text_width` and
+    // `text_height` and drawn at the stamp's own origin).
+    let (x, y) = match &config.mode {
+        WatermarkMode::Single(position) => {
+            calculate_text_position(&image, text_width as u32, text_height as u32, position)
+        }
+        WatermarkMode::Tiled { .. } => (0, 0),
+    };
+
+    let output_format = config.format.unwrap_or_else(|| {
+        image_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(OutputFormat::from_extension)
+            .unwrap_or(OutputFormat::Png)
+    });
+    let output_path = compute_output_path(image_path, options, output_format);
+
+    if options.dry_run {
+        match &config.mode {
+            WatermarkMode::Single(_) => println!(
+                "[dry-run] {} -> {} (text position: {}, {})",
+                image_path.display(),
+                output_path.display(),
+                x,
+                y
+            ),
+            WatermarkMode::Tiled { angle, spacing_x, spacing_y } => println!(
+                "[dry-run] {} -> {} (tiled, angle: {}, spacing: {}x{})",
+                image_path.display(),
+                output_path.display(),
+                angle,
+                spacing_x,
+                spacing_y
+            ),
+        }
+        return Ok(());
+    }
 
     // Convert image to RGBA if needed
     let mut rgba_image = image.to_rgba8();
 
-    // Draw text with Unicode support
-    draw_text_mut(
-        &mut rgba_image, 
-        Rgba([config.color.r, config.color.g, config.color.b, config.color.a]), 
-        x, 
-        y, 
-        scale, 
-        &font, 
-        &config.text
-    );
-
-    // Save the modified image
-    let output_path = image_path.with_file_name(
-        format!("watermarked_{}", image_path.file_name().unwrap().to_str().unwrap())
-    );
-    rgba_image.save(output_path)?;
+    match &config.mode {
+        WatermarkMode::Single(_) => draw_text(&mut rgba_image, &renderer, x, y, config),
+        WatermarkMode::Tiled { angle, spacing_x, spacing_y } => {
+            // Render the text once onto a small transparent stamp, rotate
+            // it, then alpha-blend copies of it across the whole image.
+            let mut stamp = image::RgbaImage::new(text_width.max(1) as u32, text_height.max(1) as u32);
+            draw_text(&mut stamp, &renderer, 0, 0, config);
+            let rotated = tile::rotate_stamp(&stamp, *angle);
+            tile::tile_onto(&mut rgba_image, &rotated, *spacing_x, *spacing_y);
+        }
+    }
+
+    if let Some(dir) = &options.output_dir {
+        fs::create_dir_all(dir).map_err(|e| {
+            RustyMarkError::Other(format!("failed to create output directory {}: {}", dir.display(), e))
+        })?;
+    }
+
+    // Save the modified image, converting format and flattening transparency
+    // as needed
+    format::encode(
+        &rgba_image,
+        output_format,
+        &config.background,
+        config.quality,
+        &output_path,
+    )?;
 
     Ok(())
 }
 
-// Process images in a directory or a single file
+// Process images in a directory or a single file, returning a summary of
+// how many files succeeded/failed (and why).
 fn process_images(
-    input_path: &Path, 
-    config_path: &Path
-) -> Result<(), Box<dyn Error>> {
-    // Parse configuration
-    let config = parse_config(config_path)?;
+    input_path: &Path,
+    config: &CopyrightConfig,
+    options: &RunOptions,
+) -> Result<BatchSummary, RustyMarkError> {
+    let mut summary = BatchSummary::default();
 
     // Check if input is a directory or a file
     if input_path.is_dir() {
         // Process all image files in the directory
-        for entry in fs::read_dir(input_path)? {
-            let entry = entry?;
+        for entry in fs::read_dir(input_path).map_err(|e| RustyMarkError::Other(format!(
+            "failed to read directory {}: {}",
+            input_path.display(),
+            e
+        )))? {
+            let entry = entry.map_err(|e| RustyMarkError::Other(e.to_string()))?;
             let path = entry.path();
-            
+
             // Check if it's an image file
             if path.is_file() && is_image_file(&path) {
                 // Add visual watermark
-                if let Err(e) = add_copyright_text_image(&path, &config) {
-                    eprintln!("Error processing visual watermark {}: {}", path.display(), e);
+                match add_copyright_text_image(&path, config, options) {
+                    Ok(()) => summary.record_success(),
+                    Err(e) => {
+                        if options.fail_fast {
+                            return Err(e);
+                        }
+                        summary.record_failure(path.clone(), e);
+                    }
                 }
-                
+
                 // Add metadata copyright
                 /*
                 if let Err(e) = add_copyright_metadata(&path, &config) {
@@ -215,36 +408,68 @@ fn process_images(
         }
     } else if input_path.is_file() && is_image_file(input_path) {
         // Process single image file
-        add_copyright_text_image(input_path, &config)?;
+        match add_copyright_text_image(input_path, config, options) {
+            Ok(()) => summary.record_success(),
+            Err(e) => {
+                if options.fail_fast {
+                    return Err(e);
+                }
+                summary.record_failure(input_path.to_path_buf(), e);
+            }
+        }
     } else {
-        return Err("Invalid input path".into());
+        return Err(RustyMarkError::Other(format!(
+            "invalid input path: {}",
+            input_path.display()
+        )));
     }
 
-    Ok(())
+    Ok(summary)
 }
 
 // Helper function to check if a file is an image
 fn is_image_file(path: &Path) -> bool {
     if let Some(ext) = path.extension() {
-        let ext = ext.to_str().unwrap_or("").to_lowercase();
-        ["jpg", "jpeg", "png", "gif", "bmp", "webp"].contains(&ext.as_str())
+        format::is_supported_extension(ext.to_str().unwrap_or(""))
     } else {
         false
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Parse command-line arguments
-    let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() != 3 {
-        eprintln!("Usage: {} <image_file_or_directory> <config_file>", args[0]);
-        std::process::exit(1);
-    }
+// Load the config, run the batch, and report how many files succeeded/failed as
+// an exit code: 0 if everything succeeded, 1 otherwise.
+fn run(cli: Cli) -> Result<i32, Box<dyn Error>> {
+    let mut config = match &cli.config {
+        Some(config_path) => parse_config(config_path)?,
+        None => default_config(),
+    };
+    apply_cli_overrides(&mut config, &cli);
+
+    let options = RunOptions {
+        output_dir: cli.output_dir.clone(),
+        prefix: cli.prefix.clone(),
+        dry_run: cli.dry_run,
+        fail_fast: cli.fail_fast,
+    };
 
     // Process images
-    process_images(Path::new(&args[1]), Path::new(&args[2]))?;
+    let summary = process_images(&cli.input, &config, &options)?;
+    summary.print();
 
-    println!("Copyright watermark added successfully!");
-    Ok(())
-}
\ No newline at end of file
+    Ok(if summary.failed.is_empty() { 0 } else { 1 })
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    // Printed via `Display` (not the `Debug` output `Result`'s `Termination`
+    // impl would use) so a failure names the offending file and cause
+    // instead of dumping the `RustyMarkError` variant's internals.
+    match run(cli) {
+        Ok(exit_code) => std::process::exit(exit_code),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use image::{Rgba, RgbaImage};
+
+use crate::blend::blend_pixel;
+
+/// Rotate `stamp` by `angle_degrees` about its own center, returning a new
+/// (generally larger) canvas sized to fit the full rotated extent without
+/// clipping. Uses an inverse-mapped affine transform with bilinear sampling;
+/// pixels that land outside the source stamp are left fully transparent.
+pub fn rotate_stamp(stamp: &RgbaImage, angle_degrees: f32) -> RgbaImage {
+    if angle_degrees == 0.0 {
+        return stamp.clone();
+    }
+
+    let (src_w, src_h) = stamp.dimensions();
+    let angle = angle_degrees.to_radians();
+    let (sin, cos) = angle.sin_cos();
+
+    // Bounding box of the rotated rectangle.
+    let dst_w = (src_w as f32 * cos.abs() + src_h as f32 * sin.abs()).ceil() as u32;
+    let dst_h = (src_w as f32 * sin.abs() + src_h as f32 * cos.abs()).ceil() as u32;
+
+    let src_cx = src_w as f32 / 2.0;
+    let src_cy = src_h as f32 / 2.0;
+    let dst_cx = dst_w as f32 / 2.0;
+    let dst_cy = dst_h as f32 / 2.0;
+
+    let mut rotated = RgbaImage::new(dst_w.max(1), dst_h.max(1));
+
+    for dy in 0..dst_h {
+        for dx in 0..dst_w {
+            // Inverse rotation: map destination pixel back into source space.
+            let tx = dx as f32 - dst_cx;
+            let ty = dy as f32 - dst_cy;
+            let sx = tx * cos + ty * sin + src_cx;
+            let sy = -tx * sin + ty * cos + src_cy;
+
+            if let Some(pixel) = sample_bilinear(stamp, sx, sy) {
+                rotated.put_pixel(dx, dy, pixel);
+            }
+        }
+    }
+
+    rotated
+}
+
+fn sample_bilinear(image: &RgbaImage, x: f32, y: f32) -> Option<Rgba<u8>> {
+    let (width, height) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x >= width as f32 - 1.0 || y >= height as f32 - 1.0 {
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            return None;
+        }
+        return Some(*image.get_pixel(x as u32, y as u32));
+    }
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+
+    let p00 = image.get_pixel(x0 as u32, y0 as u32);
+    let p10 = image.get_pixel(x0 as u32 + 1, y0 as u32);
+    let p01 = image.get_pixel(x0 as u32, y0 as u32 + 1);
+    let p11 = image.get_pixel(x0 as u32 + 1, y0 as u32 + 1);
+
+    let lerp_channel = |a: u8, b: u8, t: f32| -> f32 { a as f32 + (b as f32 - a as f32) * t };
+
+    let mut channels = [0u8; 4];
+    for c in 0..4 {
+        let top = lerp_channel(p00[c], p10[c], fx);
+        let bottom = lerp_channel(p01[c], p11[c], fx);
+        channels[c] = (top + (bottom - top) * fy).round() as u8;
+    }
+
+    Some(Rgba(channels))
+}
+
+/// Alpha-blend copies of `stamp` across the whole of `target` on a grid
+/// spaced `spacing_x`/`spacing_y` pixels apart, offsetting the starting
+/// tile so the pattern still covers the edges and corners.
+pub fn tile_onto(target: &mut RgbaImage, stamp: &RgbaImage, spacing_x: u32, spacing_y: u32) {
+    let (target_w, target_h) = target.dimensions();
+    let (stamp_w, stamp_h) = stamp.dimensions();
+    if stamp_w == 0 || stamp_h == 0 {
+        return;
+    }
+
+    let step_x = spacing_x.max(1) as i64;
+    let step_y = spacing_y.max(1) as i64;
+
+    let mut origin_y = -(stamp_h as i64);
+    while origin_y < target_h as i64 {
+        let mut origin_x = -(stamp_w as i64);
+        while origin_x < target_w as i64 {
+            blend_stamp_at(target, stamp, origin_x, origin_y);
+            origin_x += step_x;
+        }
+        origin_y += step_y;
+    }
+}
+
+fn blend_stamp_at(target: &mut RgbaImage, stamp: &RgbaImage, origin_x: i64, origin_y: i64) {
+    let (target_w, target_h) = target.dimensions();
+    let (stamp_w, stamp_h) = stamp.dimensions();
+
+    for sy in 0..stamp_h {
+        let dest_y = origin_y + sy as i64;
+        if dest_y < 0 || dest_y >= target_h as i64 {
+            continue;
+        }
+        for sx in 0..stamp_w {
+            let dest_x = origin_x + sx as i64;
+            if dest_x < 0 || dest_x >= target_w as i64 {
+                continue;
+            }
+
+            let pixel = *stamp.get_pixel(sx, sy);
+            if pixel[3] == 0 {
+                continue;
+            }
+            blend_pixel(target, dest_x as u32, dest_y as u32, pixel);
+        }
+    }
+}